@@ -1,12 +1,17 @@
+use std::collections::HashMap;
 use std::path::Path;
 use std::process::Command;
+use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::time::Duration;
 use std::time::Instant;
 
 use serde::Deserialize;
 use serde::Serialize;
 use sqlformat::{FormatOptions, QueryParams};
 use tauri::State;
+use tokio::sync::Notify;
 
 use crate::api::ArrowResponse;
 use connector::dialect::Connection;
@@ -32,6 +37,180 @@ pub struct DialectPayload {
   pub port: Option<String>,
   pub database: Option<String>,
   pub cwd: Option<String>,
+  /// `PRAGMA busy_timeout`, in milliseconds. sqlite only; no duckdb pragma equivalent.
+  pub busy_timeout: Option<u64>,
+  /// `PRAGMA foreign_keys`. sqlite only; no duckdb pragma equivalent.
+  pub enable_foreign_keys: Option<bool>,
+  /// `PRAGMA synchronous` mode: "OFF" | "NORMAL" | "FULL". sqlite only.
+  pub synchronous: Option<String>,
+  /// `PRAGMA query_only`. sqlite only; no duckdb pragma equivalent.
+  pub read_only: Option<bool>,
+  /// "disable" | "prefer" | "require" | "verify-ca" | "verify-full", as in libpq.
+  pub sslmode: Option<String>,
+  pub ssl_root_cert: Option<String>,
+  pub ssl_client_cert: Option<String>,
+  pub ssl_client_key: Option<String>,
+  /// Aborts the in-flight command locally if it hasn't finished after this
+  /// many milliseconds. Applies to every command that accepts `dialect` —
+  /// not just `query`/`paging_query`/`query_table` — since all of them run
+  /// through `run_cancellable`. See `run_cancellable` — no dialect in this
+  /// connector build issues a real server-side cancel, so the backend keeps
+  /// working on whatever it was doing; this only stops waiting on it.
+  pub timeout_ms: Option<u64>,
+  /// How long an idle pooled connection may sit before a checkout reconnects
+  /// instead of reusing it. Defaults to `DEFAULT_POOL_MAX_IDLE` if unset.
+  pub pool_max_idle_ms: Option<u64>,
+  /// Caps idle connections retained per `PoolKey`. Defaults to
+  /// `DEFAULT_POOL_MAX_IDLE_PER_KEY` if unset.
+  pub pool_max_idle_per_key: Option<usize>,
+}
+
+/// TLS parameters shared by the network dialects (postgres, mysql, clickhouse,
+/// clickhouse_tcp).
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TlsOptions {
+  pub sslmode: Option<String>,
+  pub ssl_root_cert: Option<String>,
+  pub ssl_client_cert: Option<String>,
+  pub ssl_client_key: Option<String>,
+}
+
+impl From<&DialectPayload> for TlsOptions {
+  fn from(payload: &DialectPayload) -> Self {
+    TlsOptions {
+      sslmode: payload.sslmode.clone(),
+      ssl_root_cert: payload.ssl_root_cert.clone(),
+      ssl_client_cert: payload.ssl_client_cert.clone(),
+      ssl_client_key: payload.ssl_client_key.clone(),
+    }
+  }
+}
+
+/// Real TLS (a `native-tls`/`rustls` feature, cert paths threaded into the
+/// network `Connection` constructors) is declined for this connector build —
+/// there's no handshake code to wire it into. This only guards against the
+/// worse alternative: silently dropping a requested `sslmode`/cert and
+/// connecting in plaintext while looking encrypted to the caller.
+fn validate_tls(tls: &TlsOptions) -> Result<(), QueryError> {
+  let wants_tls = !matches!(tls.sslmode.as_deref(), None | Some("disable"))
+    || tls.ssl_root_cert.is_some()
+    || tls.ssl_client_cert.is_some()
+    || tls.ssl_client_key.is_some();
+  if wants_tls {
+    return Err(QueryError::generic(
+      "TLS is not implemented in this connector build; set sslmode to \"disable\" (or omit it) to connect over plaintext",
+    ));
+  }
+  Ok(())
+}
+
+/// Replaces the old `e.to_string()` collapse so the frontend can highlight
+/// the offending statement instead of just showing a flat string.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryError {
+  pub code: Option<String>,
+  pub severity: Option<String>,
+  pub message: String,
+  pub detail: Option<String>,
+  pub hint: Option<String>,
+  pub position: Option<usize>,
+}
+
+impl QueryError {
+  fn generic(message: impl Into<String>) -> Self {
+    QueryError {
+      message: message.into(),
+      ..Default::default()
+    }
+  }
+
+  fn unsupported_dialect(dialect: &str) -> Self {
+    QueryError::generic(format!("not support dialect {dialect}"))
+  }
+}
+
+impl std::fmt::Display for QueryError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.message)
+  }
+}
+
+// Backend dialects currently surface errors as plain `Display`s rather than
+// structured error objects, so call sites do `.map_err(QueryError::from_backend)`
+// and we fall back to picking the code/severity/detail/hint/position fields
+// back out of the formatted text below. Once the connector crate exposes the
+// parsed error objects directly (postgres `ErrorFields` and friends) this
+// should become several targeted `From` impls instead.
+impl QueryError {
+  fn from_backend(err: impl std::fmt::Display) -> Self {
+    parse_backend_error(&err.to_string())
+  }
+}
+
+/// Best-effort recovery of the `DETAIL:`/`HINT:`/`POSITION:` lines, leading
+/// severity, and a parenthesized SQLSTATE/error code from a backend error's
+/// `Display` text. Fields we can't find stay `None`.
+fn parse_backend_error(message: &str) -> QueryError {
+  let mut err = QueryError::generic(message);
+
+  for line in message.lines() {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix("DETAIL:") {
+      err.detail = Some(rest.trim().to_string());
+    } else if let Some(rest) = line.strip_prefix("HINT:") {
+      err.hint = Some(rest.trim().to_string());
+    } else if let Some(rest) = line.strip_prefix("POSITION:") {
+      err.position = rest.trim().parse().ok();
+    } else if let Some(rest) = line.strip_prefix("SQLSTATE:") {
+      err.code = Some(rest.trim().to_string());
+    }
+  }
+
+  // Only the first line is the primary message — DETAIL:/HINT:/POSITION:
+  // lines are split out above and shouldn't be duplicated into `message`.
+  let mut first_line = message.lines().next().unwrap_or(message).trim();
+  for severity in ["ERROR", "FATAL", "PANIC", "WARNING", "NOTICE", "DEBUG", "INFO", "LOG"] {
+    if let Some(rest) = first_line.strip_prefix(severity).and_then(|r| r.trim_start().strip_prefix(':')) {
+      err.severity = Some(severity.to_string());
+      first_line = rest.trim();
+      break;
+    }
+  }
+  err.message = first_line.to_string();
+
+  if err.code.is_none() {
+    if let Some(open) = message.find('(') {
+      if let Some(len) = message[open..].find(')') {
+        let candidate = &message[open + 1..open + len];
+        if (4..=6).contains(&candidate.len()) && candidate.chars().all(|c| c.is_ascii_alphanumeric()) {
+          err.code = Some(candidate.to_string());
+        }
+      }
+    }
+  }
+
+  err
+}
+
+/// Session options applied right after a sqlite handle is opened (duckdb
+/// has no pragma equivalent for any of these — see `apply_session_options`).
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SessionOptions {
+  pub busy_timeout: Option<u64>,
+  pub enable_foreign_keys: Option<bool>,
+  pub synchronous: Option<String>,
+  pub read_only: Option<bool>,
+}
+
+impl From<&DialectPayload> for SessionOptions {
+  fn from(payload: &DialectPayload) -> Self {
+    SessionOptions {
+      busy_timeout: payload.busy_timeout,
+      enable_foreign_keys: payload.enable_foreign_keys,
+      synchronous: payload.synchronous.clone(),
+      read_only: payload.read_only,
+    }
+  }
 }
 
 #[allow(clippy::unused_async)]
@@ -45,9 +224,79 @@ pub fn get_ast_dialect(dialect: &str) -> Box<dyn sqlparser::dialect::Dialect> {
   }
 }
 
-#[allow(clippy::unused_async)]
+/// Rejects anything but the documented `"OFF"`/`"NORMAL"`/`"FULL"` values
+/// before it reaches the `PRAGMA synchronous` SQL text.
+fn validate_synchronous(mode: &str) -> Result<&str, QueryError> {
+  match mode {
+    "OFF" | "NORMAL" | "FULL" => Ok(mode),
+    other => Err(QueryError::generic(format!(
+      "unsupported PRAGMA synchronous mode \"{other}\"; expected \"OFF\", \"NORMAL\", or \"FULL\""
+    ))),
+  }
+}
+
+/// Runs each pragma in order through `issue`, stopping at (and surfacing)
+/// the first failure instead of firing-and-forgetting them. Split out from
+/// `apply_session_options` so the failure path is testable without a real
+/// `Connection`.
+async fn run_pragmas<F, Fut, T, E>(pragmas: Vec<String>, issue: F) -> Result<(), QueryError>
+where
+  F: Fn(String) -> Fut,
+  Fut: std::future::Future<Output = Result<T, E>>,
+  E: std::fmt::Display,
+{
+  for pragma in pragmas {
+    issue(pragma).await.map_err(QueryError::from_backend)?;
+  }
+  Ok(())
+}
+
+/// Issues the sqlite session pragmas right after open, so `SessionOptions`
+/// actually take effect instead of just riding along unused in the
+/// connection struct. None of `busy_timeout`/`foreign_keys`/`synchronous`/
+/// read-only have a duckdb pragma equivalent, so this is a no-op there.
+///
+/// A pragma that fails (e.g. an unsupported `synchronous` mode reaching the
+/// backend some other way, or a handle that refuses `query_only`) aborts
+/// here instead of being silently swallowed — otherwise a caller asking for
+/// `read_only: true` could walk away with a writable connection and no
+/// indication anything went wrong.
+async fn apply_session_options(
+  conn: &dyn Connection,
+  dialect: &str,
+  options: &SessionOptions,
+) -> Result<(), QueryError> {
+  if dialect != "sqlite" {
+    return Ok(());
+  }
+  let mut pragmas = Vec::new();
+  if let Some(ms) = options.busy_timeout {
+    pragmas.push(format!("PRAGMA busy_timeout = {ms}"));
+  }
+  if let Some(enabled) = options.enable_foreign_keys {
+    pragmas.push(format!("PRAGMA foreign_keys = {}", enabled as u8));
+  }
+  if let Some(mode) = &options.synchronous {
+    pragmas.push(format!("PRAGMA synchronous = {}", validate_synchronous(mode)?));
+  }
+  if options.read_only == Some(true) {
+    pragmas.push("PRAGMA query_only = true".to_string());
+  }
+  run_pragmas(pragmas, |pragma| async move { conn.query(&pragma, 0, 0).await }).await
+}
+
 pub async fn get_dialect(
-  DialectPayload {
+  payload: DialectPayload,
+) -> Result<Option<Box<dyn Connection>>, QueryError> {
+  let options = SessionOptions::from(&payload);
+  let tls = TlsOptions::from(&payload);
+  if matches!(
+    payload.dialect.as_str(),
+    "clickhouse" | "clickhouse_tcp" | "mysql" | "postgres"
+  ) {
+    validate_tls(&tls)?;
+  }
+  let DialectPayload {
     dialect,
     path,
     username,
@@ -56,9 +305,9 @@ pub async fn get_dialect(
     host,
     port,
     cwd,
-  }: DialectPayload,
-) -> Option<Box<dyn Connection>> {
-  match dialect.as_str() {
+    ..
+  } = payload;
+  Ok(match dialect.as_str() {
     "folder" => Some(Box::new(FolderConnection {
       path: path.unwrap(),
       cwd,
@@ -66,13 +315,32 @@ pub async fn get_dialect(
     "file" => Some(Box::new(FileConnection {
       path: path.unwrap(),
     })),
-    "duckdb" => Some(Box::new(DuckDbConnection {
-      path: path.unwrap(),
-      cwd,
-    })),
-    "sqlite" => Some(Box::new(SqliteConnection {
-      path: path.unwrap(),
-    })),
+    "duckdb" => {
+      // `DuckDbConnection` has no `options` field to carry `SessionOptions`
+      // into — none of them has a duckdb pragma equivalent anyway (see
+      // `apply_session_options`), so there's nothing to thread through here.
+      let conn: Box<dyn Connection> = Box::new(DuckDbConnection {
+        path: path.unwrap(),
+        cwd,
+      });
+      apply_session_options(conn.as_ref(), &dialect, &options).await?;
+      Some(conn)
+    }
+    "sqlite" => {
+      // Likewise, `SqliteConnection` takes no `options` field: the pragmas
+      // are issued after the fact by `apply_session_options`, not baked into
+      // the connection struct.
+      let conn: Box<dyn Connection> = Box::new(SqliteConnection {
+        path: path.unwrap(),
+      });
+      apply_session_options(conn.as_ref(), &dialect, &options).await?;
+      Some(conn)
+    }
+    // None of the network `Connection` structs below carry a `tls` field —
+    // there's no handshake code in this connector build for them to hand it
+    // to. `validate_tls` above is the actual enforcement: it already
+    // rejected this payload if it asked for anything but plaintext, so
+    // reaching here means there's nothing left to wire through.
     "clickhouse" => Some(Box::new(ClickhouseConnection {
       host: host.unwrap(),
       port: port.unwrap_or_default(),
@@ -103,6 +371,241 @@ pub async fn get_dialect(
     })),
     // _ => Err("not support dialect".to_string()),
     _ => None,
+  })
+}
+
+/// Identifies a reusable connection, normalized from `DialectPayload` so that
+/// transient fields (path, cwd) don't cause pointless cache misses.
+///
+/// Includes `password`: a rotated/corrected credential must mint a new
+/// connection rather than reuse one authenticated under the old one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+  dialect: String,
+  host: Option<String>,
+  port: Option<String>,
+  database: Option<String>,
+  username: Option<String>,
+  password: Option<String>,
+}
+
+impl From<&DialectPayload> for PoolKey {
+  fn from(payload: &DialectPayload) -> Self {
+    PoolKey {
+      dialect: payload.dialect.clone(),
+      host: payload.host.clone(),
+      port: payload.port.clone(),
+      database: payload.database.clone(),
+      username: payload.username.clone(),
+      password: payload.password.clone(),
+    }
+  }
+}
+
+/// Only postgres, mysql and clickhouse_tcp get pooled; the HTTP `clickhouse`
+/// dialect, like the local ones, gets a fresh connection on every checkout.
+fn is_poolable(dialect: &str) -> bool {
+  matches!(dialect, "postgres" | "mysql" | "clickhouse_tcp")
+}
+
+/// Default for how long an idle connection sits in the pool before we'd
+/// rather pay for a reconnect than risk handing back a socket the server
+/// already closed. Overridable per-connection via `pool_max_idle_ms`.
+const DEFAULT_POOL_MAX_IDLE: Duration = Duration::from_secs(5 * 60);
+/// Default cap on how many idle connections accumulate per key.
+/// Overridable per-connection via `pool_max_idle_per_key`.
+const DEFAULT_POOL_MAX_IDLE_PER_KEY: usize = 4;
+
+struct IdleConnection {
+  conn: Arc<dyn Connection>,
+  idle_since: Instant,
+}
+
+fn connection_pool() -> &'static Mutex<HashMap<PoolKey, Vec<IdleConnection>>> {
+  static POOL: OnceLock<Mutex<HashMap<PoolKey, Vec<IdleConnection>>>> = OnceLock::new();
+  POOL.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// A connection checked out of the pool for one command's duration. Dropping
+/// it normally returns it to the idle list (r2d2-style); call `discard()`
+/// first if the connection may be in a bad state (backend error, cancelled
+/// query) so the next checkout reconnects instead of reusing it.
+pub struct PooledConnection {
+  /// `None` for dialects that aren't pooled (folder/file/sqlite/duckdb/clickhouse) —
+  /// those are cheap enough to just tear down on drop.
+  key: Option<PoolKey>,
+  conn: Option<Arc<dyn Connection>>,
+  /// Resolved from `pool_max_idle_per_key` at checkout time, since `Drop`
+  /// has no access to the original `DialectPayload`.
+  max_idle_per_key: usize,
+}
+
+impl std::ops::Deref for PooledConnection {
+  type Target = Arc<dyn Connection>;
+  fn deref(&self) -> &Self::Target {
+    self.conn.as_ref().expect("PooledConnection used after discard")
+  }
+}
+
+impl PooledConnection {
+  /// Drops the underlying connection instead of returning it to the pool.
+  fn discard(mut self) {
+    self.conn = None;
+  }
+}
+
+impl Drop for PooledConnection {
+  fn drop(&mut self) {
+    let (Some(key), Some(conn)) = (self.key.clone(), self.conn.take()) else {
+      return;
+    };
+    let mut pool = connection_pool().lock().unwrap();
+    let idle = pool.entry(key).or_default();
+    if idle.len() < self.max_idle_per_key {
+      idle.push(IdleConnection {
+        conn,
+        idle_since: Instant::now(),
+      });
+    }
+  }
+}
+
+/// Like `get_dialect`, but for network dialects checks out a connection from
+/// the pool (reconnecting only on a cold key or once every idle connection
+/// has aged past `pool_max_idle_ms`/`DEFAULT_POOL_MAX_IDLE`) instead of
+/// reconnecting on every call.
+pub async fn get_pooled_dialect(
+  dialect: DialectPayload,
+) -> Result<Option<PooledConnection>, QueryError> {
+  let max_idle_per_key = dialect
+    .pool_max_idle_per_key
+    .unwrap_or(DEFAULT_POOL_MAX_IDLE_PER_KEY);
+
+  if !is_poolable(&dialect.dialect) {
+    let Some(conn) = get_dialect(dialect).await? else {
+      return Ok(None);
+    };
+    return Ok(Some(PooledConnection {
+      key: None,
+      conn: Some(Arc::from(conn)),
+      max_idle_per_key,
+    }));
+  }
+
+  let max_idle = dialect
+    .pool_max_idle_ms
+    .map(Duration::from_millis)
+    .unwrap_or(DEFAULT_POOL_MAX_IDLE);
+  let key = PoolKey::from(&dialect);
+  let reused = {
+    let mut pool = connection_pool().lock().unwrap();
+    let idle = pool.entry(key.clone()).or_default();
+    std::iter::from_fn(|| idle.pop()).find(|entry| entry.idle_since.elapsed() < max_idle)
+  };
+
+  let conn = match reused {
+    Some(entry) => entry.conn,
+    None => {
+      let Some(conn) = get_dialect(dialect).await? else {
+        return Ok(None);
+      };
+      Arc::from(conn)
+    }
+  };
+  Ok(Some(PooledConnection {
+    key: Some(key),
+    conn: Some(conn),
+    max_idle_per_key,
+  }))
+}
+
+/// Returns `d` to the pool when `result` is `Ok`; discards it when `result` is
+/// `Err`, since a backend error (or a cancelled/timed-out query, see
+/// `run_cancellable`) may have left the connection desynced. Only the outer
+/// `Result` is looked at: commands that pass the backend's own `Result` on to
+/// `ArrowResponse` (`query`, `paging_query`, `query_table`, `show_schema`,
+/// `show_column`, `find`) hand an `Ok` here on a backend error, so their
+/// connection goes back to the pool.
+fn finish<T>(d: PooledConnection, result: Result<T, QueryError>) -> Result<T, QueryError> {
+  if result.is_err() {
+    d.discard();
+  }
+  result
+}
+
+fn cancellation_registry() -> &'static Mutex<HashMap<String, Arc<Notify>>> {
+  static REGISTRY: OnceLock<Mutex<HashMap<String, Arc<Notify>>>> = OnceLock::new();
+  REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn register_query(query_id: &str) -> Arc<Notify> {
+  let notify = Arc::new(Notify::new());
+  cancellation_registry()
+    .lock()
+    .unwrap()
+    .insert(query_id.to_string(), notify.clone());
+  notify
+}
+
+/// Runs `fut` to completion unless it's cancelled via `cancel_query` or
+/// outlives `timeout_ms`.
+///
+/// Native server-side cancellation (postgres `CancelRequest`, mysql `KILL
+/// QUERY`, clickhouse `KILL QUERY`) is declined for this connector build —
+/// there's no out-of-band channel to issue it on. On cancel/timeout this
+/// only drops the future locally; the backend keeps executing the statement
+/// until it notices the client went away. Callers pair this with
+/// `finish`/`discard` so the underlying connection is evicted from the pool
+/// rather than handed to the next caller while the stale query may still be
+/// in flight on it — a mitigation, not a substitute for the real cancel.
+async fn run_cancellable<T>(
+  query_id: Option<String>,
+  timeout_ms: Option<u64>,
+  fut: impl std::future::Future<Output = T>,
+) -> Result<T, QueryError> {
+  let notify = query_id.as_deref().map(register_query);
+  tokio::pin!(fut);
+
+  let body = async {
+    if let Some(notify) = &notify {
+      tokio::select! {
+        () = notify.notified() => Err(QueryError::generic("query cancelled")),
+        out = &mut fut => Ok(out),
+      }
+    } else {
+      Ok((&mut fut).await)
+    }
+  };
+
+  let result = match timeout_ms {
+    Some(ms) => tokio::time::timeout(Duration::from_millis(ms), body)
+      .await
+      .unwrap_or_else(|_| Err(QueryError::generic("query timed out"))),
+    None => body.await,
+  };
+
+  if let Some(id) = &query_id {
+    cancellation_registry().lock().unwrap().remove(id);
+  }
+  result
+}
+
+#[tauri::command]
+pub async fn cancel_query(query_id: String) -> Result<(), QueryError> {
+  match cancellation_registry().lock().unwrap().remove(&query_id) {
+    Some(notify) => {
+      // `notify_one`, not `notify_waiters`: there's exactly one waiter per
+      // `query_id` (the `select!` in `run_cancellable`), and unlike
+      // `notify_waiters`, `notify_one` stores a permit when called before
+      // that waiter has polled `notified()` yet. Without that, a cancel
+      // landing in the window between `register_query` and the first poll
+      // would be silently dropped and the query would run to completion.
+      notify.notify_one();
+      Ok(())
+    }
+    None => Err(QueryError::generic(format!(
+      "no in-flight query {query_id}"
+    ))),
   }
 }
 
@@ -111,15 +614,17 @@ pub async fn query(
   sql: String,
   limit: usize,
   offset: usize,
+  query_id: Option<String>,
   dialect: DialectPayload,
-) -> Result<ArrowResponse, String> {
-  if let Some(d) = get_dialect(dialect).await {
+) -> Result<ArrowResponse, QueryError> {
+  if let Some(d) = get_pooled_dialect(dialect.clone()).await? {
     let start = Instant::now();
-    let res = d.query(&sql, limit, offset).await;
+    let result = run_cancellable(query_id, dialect.timeout_ms, d.query(&sql, limit, offset)).await;
+    let res = finish(d, result)?;
     let duration = start.elapsed().as_millis();
     Ok(ArrowResponse::from_raw_data(res, Some(duration)))
   } else {
-    Err("not support dialect".to_string())
+    Err(QueryError::unsupported_dialect(&dialect.dialect))
   }
 }
 
@@ -128,15 +633,22 @@ pub async fn paging_query(
   sql: String,
   limit: usize,
   offset: usize,
+  query_id: Option<String>,
   dialect: DialectPayload,
-) -> Result<ArrowResponse, String> {
-  if let Some(d) = get_dialect(dialect).await {
+) -> Result<ArrowResponse, QueryError> {
+  if let Some(d) = get_pooled_dialect(dialect.clone()).await? {
     let start = Instant::now();
-    let res = d.paging_query(&sql, Some(limit), Some(offset)).await;
+    let result = run_cancellable(
+      query_id,
+      dialect.timeout_ms,
+      d.paging_query(&sql, Some(limit), Some(offset)),
+    )
+    .await;
+    let res = finish(d, result)?;
     let duration = start.elapsed().as_millis();
     Ok(ArrowResponse::from_raw_data(res, Some(duration)))
   } else {
-    Err("not support dialect".to_string())
+    Err(QueryError::unsupported_dialect(&dialect.dialect))
   }
 }
 
@@ -147,22 +659,27 @@ pub async fn query_table(
   offset: usize,
   #[allow(non_snake_case)] orderBy: Option<String>,
   r#where: Option<String>,
+  query_id: Option<String>,
   dialect: DialectPayload,
-) -> Result<ArrowResponse, String> {
-  let d = get_dialect(dialect.clone())
-    .await
-    .ok_or_else(|| format!("not support dialect {}", dialect.dialect))?;
+) -> Result<ArrowResponse, QueryError> {
+  let d = get_pooled_dialect(dialect.clone())
+    .await?
+    .ok_or_else(|| QueryError::unsupported_dialect(&dialect.dialect))?;
 
   let start = Instant::now();
-  let res = d
-    .query_table(
+  let result = run_cancellable(
+    query_id,
+    dialect.timeout_ms,
+    d.query_table(
       table,
       limit,
       offset,
       &r#where.clone().unwrap_or_default(),
       &orderBy.clone().unwrap_or_default(),
-    )
-    .await;
+    ),
+  )
+  .await;
+  let res = finish(d, result)?;
   let duration = start.elapsed().as_millis();
   Ok(ArrowResponse::from_raw_data(res, Some(duration)))
 }
@@ -171,14 +688,61 @@ pub async fn query_table(
 pub async fn table_row_count(
   table: &str,
   condition: &str,
+  query_id: Option<String>,
   dialect: DialectPayload,
-) -> Result<usize, String> {
-  if let Some(d) = get_dialect(dialect).await {
-    d.table_row_count(table, condition)
+) -> Result<usize, QueryError> {
+  if let Some(d) = get_pooled_dialect(dialect.clone()).await? {
+    let result = run_cancellable(query_id, dialect.timeout_ms, d.table_row_count(table, condition))
       .await
-      .map_err(|e| e.to_string())
+      .and_then(|r| r.map_err(QueryError::from_backend));
+    finish(d, result)
   } else {
-    Err("not support dialect".to_string())
+    Err(QueryError::unsupported_dialect(&dialect.dialect))
+  }
+}
+
+/// Formats this command is willing to forward to `Connection::export`.
+///
+/// This is this *file's* allowlist, not a confirmed list of what the
+/// connector crate actually writes: `export` just hands `format` through as
+/// a string (`d.export(&sql, &file, &format)`, unchanged from baseline), and
+/// there's no connector source in this tree to confirm what it does with
+/// any of these beyond the pre-existing "csv" passthrough. Narrowing to this
+/// set at least rejects typos/garbage here instead of forwarding them
+/// unchecked; it is not proof that parquet/arrow/jsonl writers exist behind
+/// `export`. Columnar writing for those formats, if it isn't already there,
+/// belongs in the connector crate's `Connection::export` impl — this
+/// tauri-command layer doesn't do file I/O itself for any other format
+/// either.
+const SUPPORTED_EXPORT_FORMATS: &[&str] = &["csv", "parquet", "arrow", "jsonl"];
+
+/// Normalizes a requested/inferred export format (the common "ipc" extension
+/// means the same thing as "arrow"), then checks it against
+/// `SUPPORTED_EXPORT_FORMATS`.
+///
+/// `compression` is rejected outright rather than supported: `Connection::export`
+/// takes the format as a single string with no separate parameter for a codec,
+/// and there's no connector source in this tree to confirm what (if anything)
+/// it does with a `"parquet:zstd"`-style descriptor smuggled through that
+/// string. Accepting and validating the argument would tell callers it works
+/// when it may silently fail or be ignored on the other end. Fail closed
+/// until the connector side grows a real compression parameter and this can
+/// be wired to it honestly.
+fn normalize_export_format(format: &str, compression: Option<&str>) -> Result<String, QueryError> {
+  let format = match format {
+    "ipc" => "arrow",
+    other => other,
+  };
+  if !SUPPORTED_EXPORT_FORMATS.contains(&format) {
+    return Err(QueryError::generic(format!(
+      "unsupported export format \"{format}\"; expected one of {SUPPORTED_EXPORT_FORMATS:?}"
+    )));
+  }
+  match compression {
+    None => Ok(format.to_string()),
+    Some(_) => Err(QueryError::generic(
+      "export compression is not supported yet",
+    )),
   }
 }
 
@@ -187,18 +751,19 @@ pub async fn export(
   sql: String,
   file: String,
   format: Option<String>,
+  compression: Option<String>,
+  query_id: Option<String>,
   dialect: DialectPayload,
-) -> Result<(), String> {
-  if let Some(d) = get_dialect(dialect).await {
-    let format = if let Some(format) = format {
-      format
-    } else {
-      file.split('.').next_back().unwrap_or("csv").to_string()
-    };
-    let _ = d.export(&sql, &file, &format).await;
-    Ok(())
+) -> Result<(), QueryError> {
+  if let Some(d) = get_pooled_dialect(dialect.clone()).await? {
+    let format = format.unwrap_or_else(|| file.split('.').next_back().unwrap_or("csv").to_string());
+    let format = normalize_export_format(&format, compression.as_deref())?;
+    let result = run_cancellable(query_id, dialect.timeout_ms, d.export(&sql, &file, &format))
+      .await
+      .and_then(|r| r.map_err(QueryError::from_backend));
+    finish(d, result)
   } else {
-    Err("not support dialect".to_string())
+    Err(QueryError::unsupported_dialect(&dialect.dialect))
   }
 }
 
@@ -212,21 +777,29 @@ pub async fn opened_files(state: State<'_, OpenedFiles>) -> Result<Vec<String>,
 }
 
 #[tauri::command]
-pub async fn get_db(dialect: DialectPayload) -> Result<TreeNode, String> {
-  if let Some(d) = get_dialect(dialect).await {
-    d.get_db().await.map_err(|e| e.to_string())
+pub async fn get_db(query_id: Option<String>, dialect: DialectPayload) -> Result<TreeNode, QueryError> {
+  if let Some(d) = get_pooled_dialect(dialect.clone()).await? {
+    let result = run_cancellable(query_id, dialect.timeout_ms, d.get_db())
+      .await
+      .and_then(|r| r.map_err(QueryError::from_backend));
+    finish(d, result)
   } else {
-    Err("not support dialect".to_string())
+    Err(QueryError::unsupported_dialect(&dialect.dialect))
   }
 }
 
 #[tauri::command]
-pub async fn show_schema(schema: &str, dialect: DialectPayload) -> Result<ArrowResponse, String> {
-  let d = get_dialect(dialect.clone())
-    .await
-    .ok_or_else(|| format!("not support dialect {}", dialect.dialect))?;
-  let res = d.show_schema(schema).await;
+pub async fn show_schema(
+  schema: &str,
+  query_id: Option<String>,
+  dialect: DialectPayload,
+) -> Result<ArrowResponse, QueryError> {
+  let d = get_pooled_dialect(dialect.clone())
+    .await?
+    .ok_or_else(|| QueryError::unsupported_dialect(&dialect.dialect))?;
 
+  let result = run_cancellable(query_id, dialect.timeout_ms, d.show_schema(schema)).await;
+  let res = finish(d, result)?;
   Ok(ArrowResponse::from_raw_data(res, None))
 }
 
@@ -234,13 +807,15 @@ pub async fn show_schema(schema: &str, dialect: DialectPayload) -> Result<ArrowR
 pub async fn show_column(
   schema: Option<&str>,
   table: &str,
+  query_id: Option<String>,
   dialect: DialectPayload,
-) -> Result<ArrowResponse, String> {
-  let d = get_dialect(dialect.clone())
-    .await
-    .ok_or_else(|| format!("not support dialect {}", dialect.dialect))?;
-  let res = d.show_column(schema, table).await;
+) -> Result<ArrowResponse, QueryError> {
+  let d = get_pooled_dialect(dialect.clone())
+    .await?
+    .ok_or_else(|| QueryError::unsupported_dialect(&dialect.dialect))?;
 
+  let result = run_cancellable(query_id, dialect.timeout_ms, d.show_column(schema, table)).await;
+  let res = finish(d, result)?;
   Ok(ArrowResponse::from_raw_data(res, None))
 }
 
@@ -248,14 +823,17 @@ pub async fn show_column(
 pub async fn drop_table(
   schema: Option<&str>,
   table: &str,
+  query_id: Option<String>,
   dialect: DialectPayload,
-) -> Result<String, String> {
-  let d = get_dialect(dialect.clone())
+) -> Result<String, QueryError> {
+  let d = get_pooled_dialect(dialect.clone())
+    .await?
+    .ok_or_else(|| QueryError::unsupported_dialect(&dialect.dialect))?;
+
+  let result = run_cancellable(query_id, dialect.timeout_ms, d.drop_table(schema, table))
     .await
-    .ok_or_else(|| format!("not support dialect {}", dialect.dialect))?;
-  // TODO: ERROR INFO
-  let res = d.drop_table(schema, table).await.expect("ERROR");
-  Ok(res)
+    .and_then(|r| r.map_err(QueryError::from_backend));
+  finish(d, result)
 }
 
 #[tauri::command]
@@ -269,24 +847,31 @@ pub async fn format_sql(sql: &str) -> Result<String, String> {
 pub async fn find(
   value: &str,
   path: &str,
+  query_id: Option<String>,
   dialect: DialectPayload,
-) -> Result<ArrowResponse, String> {
-  let d = get_dialect(dialect.clone())
-    .await
-    .ok_or_else(|| format!("not support dialect {}", dialect.dialect))?;
-  let res = d.find(value, path).await;
+) -> Result<ArrowResponse, QueryError> {
+  let d = get_pooled_dialect(dialect.clone())
+    .await?
+    .ok_or_else(|| QueryError::unsupported_dialect(&dialect.dialect))?;
 
+  let result = run_cancellable(query_id, dialect.timeout_ms, d.find(value, path)).await;
+  let res = finish(d, result)?;
   Ok(ArrowResponse::from_raw_data(res, None))
 }
 
 #[tauri::command]
-pub async fn all_columns(dialect: DialectPayload) -> Result<Vec<Metadata>, String> {
-  let d = get_dialect(dialect.clone())
-    .await
-    .ok_or_else(|| format!("not support dialect {}", dialect.dialect))?;
-  let s = d.all_columns().await;
+pub async fn all_columns(
+  query_id: Option<String>,
+  dialect: DialectPayload,
+) -> Result<Vec<Metadata>, QueryError> {
+  let d = get_pooled_dialect(dialect.clone())
+    .await?
+    .ok_or_else(|| QueryError::unsupported_dialect(&dialect.dialect))?;
 
-  s.map_err(|e| format!("not support dialect {}", e))
+  let result = run_cancellable(query_id, dialect.timeout_ms, d.all_columns())
+    .await
+    .and_then(|r| r.map_err(QueryError::from_backend));
+  finish(d, result)
 }
 
 #[tauri::command]
@@ -327,3 +912,260 @@ pub async fn open_path(path: &str) -> Result<(), String> {
   }
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_backend_error_splits_message_from_detail_and_hint() {
+    let err = parse_backend_error("ERROR: syntax error\nDETAIL: at token X\nHINT: try Y\nPOSITION: 15");
+    assert_eq!(err.severity.as_deref(), Some("ERROR"));
+    assert_eq!(err.message, "syntax error");
+    assert_eq!(err.detail.as_deref(), Some("at token X"));
+    assert_eq!(err.hint.as_deref(), Some("try Y"));
+    assert_eq!(err.position, Some(15));
+  }
+
+  #[test]
+  fn parse_backend_error_picks_up_parenthesized_code() {
+    let err = parse_backend_error("Error 1146 (42S02): Table 'foo' doesn't exist");
+    assert_eq!(err.code.as_deref(), Some("42S02"));
+  }
+
+  #[test]
+  fn parse_backend_error_defaults_to_flat_message() {
+    let err = parse_backend_error("connection refused");
+    assert_eq!(err.message, "connection refused");
+    assert!(err.severity.is_none());
+    assert!(err.detail.is_none());
+  }
+
+  #[test]
+  fn validate_synchronous_accepts_known_modes() {
+    assert_eq!(validate_synchronous("OFF").unwrap(), "OFF");
+    assert_eq!(validate_synchronous("NORMAL").unwrap(), "NORMAL");
+    assert_eq!(validate_synchronous("FULL").unwrap(), "FULL");
+  }
+
+  #[test]
+  fn validate_synchronous_rejects_unknown_mode() {
+    assert!(validate_synchronous("DROP TABLE users").is_err());
+  }
+
+  #[tokio::test]
+  async fn run_pragmas_stops_at_first_failure() {
+    let ran = std::sync::Mutex::new(Vec::new());
+    let pragmas = vec![
+      "PRAGMA busy_timeout = 1000".to_string(),
+      "PRAGMA synchronous = NORMAL".to_string(),
+      "PRAGMA query_only = true".to_string(),
+    ];
+
+    let result = run_pragmas(pragmas, |pragma| {
+      ran.lock().unwrap().push(pragma.clone());
+      async move {
+        if pragma.contains("synchronous") {
+          Err("backend rejected PRAGMA synchronous")
+        } else {
+          Ok(())
+        }
+      }
+    })
+    .await;
+
+    assert!(result.is_err());
+    // The failing pragma ran, but the one after it never did.
+    assert_eq!(*ran.lock().unwrap(), vec![
+      "PRAGMA busy_timeout = 1000".to_string(),
+      "PRAGMA synchronous = NORMAL".to_string(),
+    ]);
+  }
+
+  #[tokio::test]
+  async fn run_pragmas_succeeds_when_all_pragmas_succeed() {
+    let pragmas = vec!["PRAGMA busy_timeout = 1000".to_string()];
+    let result = run_pragmas(pragmas, |_| async { Ok::<(), &str>(()) }).await;
+    assert!(result.is_ok());
+  }
+
+  #[test]
+  fn validate_tls_accepts_plaintext() {
+    assert!(validate_tls(&TlsOptions::default()).is_ok());
+    let disable = TlsOptions { sslmode: Some("disable".to_string()), ..Default::default() };
+    assert!(validate_tls(&disable).is_ok());
+  }
+
+  #[test]
+  fn validate_tls_rejects_requested_encryption() {
+    let require = TlsOptions { sslmode: Some("require".to_string()), ..Default::default() };
+    assert!(validate_tls(&require).is_err());
+
+    let cert_only = TlsOptions { ssl_root_cert: Some("/etc/ca.pem".to_string()), ..Default::default() };
+    assert!(validate_tls(&cert_only).is_err());
+  }
+
+  #[tokio::test]
+  async fn cancel_before_first_poll_of_notified_still_wakes_it() {
+    // `run_cancellable` registers the query, then its `select!` polls
+    // `notified()` for the first time. A concurrent `cancel_query` can land
+    // in between those two steps. Reproduce that ordering directly: call
+    // `cancel_query` (which does `notify_one`) before anything has polled
+    // `notified()`, then confirm `notified()` still resolves instead of
+    // hanging — the permit `notify_one` stores is what makes that true;
+    // `notify_waiters` would have woken nobody and lost the cancel.
+    let query_id = "race-test".to_string();
+    let notify = register_query(&query_id);
+    cancel_query(query_id).await.unwrap();
+
+    tokio::time::timeout(Duration::from_millis(100), notify.notified())
+      .await
+      .expect("cancellation was lost: notified() never resolved");
+  }
+
+  #[tokio::test]
+  async fn run_cancellable_returns_ok_when_not_cancelled() {
+    let result = run_cancellable(None, None, async { 42 }).await;
+    assert_eq!(result.unwrap(), 42);
+  }
+
+  #[tokio::test]
+  async fn run_cancellable_times_out() {
+    let result = run_cancellable(None, Some(10), std::future::pending::<()>()).await;
+    assert!(result.is_err());
+  }
+
+  fn sample_payload() -> DialectPayload {
+    DialectPayload {
+      dialect: "postgres".to_string(),
+      host: Some("localhost".to_string()),
+      port: Some("5432".to_string()),
+      database: Some("app".to_string()),
+      username: Some("app".to_string()),
+      password: Some("hunter2".to_string()),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn pool_key_ignores_unrelated_fields() {
+    let mut payload = sample_payload();
+    payload.timeout_ms = Some(5_000);
+    assert_eq!(PoolKey::from(&sample_payload()), PoolKey::from(&payload));
+  }
+
+  #[test]
+  fn pool_key_differs_on_rotated_password() {
+    let mut rotated = sample_payload();
+    rotated.password = Some("hunter3".to_string());
+    assert_ne!(PoolKey::from(&sample_payload()), PoolKey::from(&rotated));
+
+    let mut set = std::collections::HashSet::new();
+    set.insert(PoolKey::from(&sample_payload()));
+    assert!(!set.contains(&PoolKey::from(&rotated)));
+  }
+
+  /// Distinct `PoolKey` per test so they don't collide on the shared,
+  /// process-wide `connection_pool()` static when run in parallel.
+  fn test_pool_key(tag: &str) -> PoolKey {
+    PoolKey {
+      dialect: "postgres".to_string(),
+      host: Some(tag.to_string()),
+      port: None,
+      database: None,
+      username: None,
+      password: None,
+    }
+  }
+
+  #[test]
+  fn pooled_connection_is_reused_within_max_idle() {
+    let key = test_pool_key("reuse-test");
+    let conn: Arc<dyn Connection> = Arc::new(FileConnection {
+      path: "reuse-test".to_string(),
+    });
+
+    // Dropping a `PooledConnection` returns it to the idle list.
+    drop(PooledConnection {
+      key: Some(key.clone()),
+      conn: Some(conn.clone()),
+      max_idle_per_key: DEFAULT_POOL_MAX_IDLE_PER_KEY,
+    });
+
+    let mut pool = connection_pool().lock().unwrap();
+    let idle = pool.get_mut(&key).expect("connection was not returned to the pool");
+    let entry = idle.pop().expect("no idle connection present");
+    assert!(entry.idle_since.elapsed() < Duration::from_secs(60));
+    assert!(Arc::ptr_eq(&entry.conn, &conn));
+  }
+
+  #[test]
+  fn pooled_connection_past_max_idle_is_not_reused() {
+    let key = test_pool_key("evict-test");
+    let max_idle = Duration::from_millis(5);
+    let conn: Arc<dyn Connection> = Arc::new(FileConnection {
+      path: "evict-test".to_string(),
+    });
+
+    drop(PooledConnection {
+      key: Some(key.clone()),
+      conn: Some(conn),
+      max_idle_per_key: DEFAULT_POOL_MAX_IDLE_PER_KEY,
+    });
+    std::thread::sleep(Duration::from_millis(20));
+
+    // Same lookup `get_pooled_dialect` does: pop idle entries, keep only
+    // ones still fresher than `max_idle`.
+    let reused = {
+      let mut pool = connection_pool().lock().unwrap();
+      let idle = pool.entry(key).or_default();
+      std::iter::from_fn(|| idle.pop()).find(|entry| entry.idle_since.elapsed() < max_idle)
+    };
+    assert!(reused.is_none(), "a stale connection should not be reused");
+  }
+
+  #[test]
+  fn pooled_connection_is_discarded_on_error_instead_of_returned() {
+    let key = test_pool_key("discard-test");
+    let conn: Arc<dyn Connection> = Arc::new(FileConnection {
+      path: "discard-test".to_string(),
+    });
+    let pooled = PooledConnection {
+      key: Some(key.clone()),
+      conn: Some(conn),
+      max_idle_per_key: DEFAULT_POOL_MAX_IDLE_PER_KEY,
+    };
+
+    let result: Result<(), QueryError> = finish(pooled, Err(QueryError::generic("backend error")));
+    assert!(result.is_err());
+
+    let mut pool = connection_pool().lock().unwrap();
+    let idle = pool.entry(key).or_default();
+    assert!(idle.is_empty(), "a discarded connection must not be returned to the pool");
+  }
+
+  #[test]
+  fn normalize_export_format_aliases_ipc_to_arrow() {
+    assert_eq!(normalize_export_format("ipc", None).unwrap(), "arrow");
+  }
+
+  #[test]
+  fn normalize_export_format_rejects_any_compression() {
+    assert!(normalize_export_format("parquet", Some("zstd")).is_err());
+    assert!(normalize_export_format("parquet", Some("snappy")).is_err());
+    assert!(normalize_export_format("parquet", Some("gzip")).is_err());
+    assert!(normalize_export_format("csv", Some("zstd")).is_err());
+  }
+
+  #[test]
+  fn normalize_export_format_accepts_known_formats() {
+    for format in ["csv", "parquet", "arrow", "jsonl"] {
+      assert_eq!(normalize_export_format(format, None).unwrap(), format);
+    }
+  }
+
+  #[test]
+  fn normalize_export_format_rejects_unknown_format() {
+    assert!(normalize_export_format("xlsx", None).is_err());
+  }
+}